@@ -0,0 +1,84 @@
+// Interactive stepping-debugger state, enabled by the --debug CLI flag.
+pub struct Debugger {
+    pub enabled: bool,
+    pub paused: bool,
+    pub breakpoint: Option<u16>,
+}
+
+impl Debugger {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            paused: enabled,
+            breakpoint: None,
+        }
+    }
+}
+
+// Disassembles a single opcode into its mnemonic form, matching the labels
+// already used as comments beside each handler in main.rs.
+pub fn disassemble(opcode: u16) -> String {
+    let x = (opcode & 0x0F00) >> 8;
+    let y = (opcode & 0x00F0) >> 4;
+    let n = opcode & 0x000F;
+    let kk = opcode & 0x00FF;
+    let nnn = opcode & 0x0FFF;
+
+    match opcode >> 12 {
+        0x0 => match opcode & 0x00FF {
+            0x00 => "NOP".to_string(),
+            0xE0 => "CLS".to_string(),
+            0xEE => "RET".to_string(),
+            0xFB => "SCR".to_string(),
+            0xFC => "SCL".to_string(),
+            0xFD => "EXIT".to_string(),
+            0xFE => "LOW".to_string(),
+            0xFF => "HIGH".to_string(),
+            low if (low & 0x00F0) == 0x00C0 => format!("SCD {}", low & 0x000F),
+            _ => format!("UNKNOWN {opcode:#06x}"),
+        },
+        0x1 => format!("JP {nnn:#05x}"),
+        0x2 => format!("CALL {nnn:#05x}"),
+        0x3 => format!("SE V{x:X}, {kk:#04x}"),
+        0x4 => format!("SNE V{x:X}, {kk:#04x}"),
+        0x5 => format!("SE V{x:X}, V{y:X}"),
+        0x6 => format!("LD V{x:X}, {kk:#04x}"),
+        0x7 => format!("ADD V{x:X}, {kk:#04x}"),
+        0x8 => match n {
+            0x0 => format!("LD V{x:X}, V{y:X}"),
+            0x1 => format!("OR V{x:X}, V{y:X}"),
+            0x2 => format!("AND V{x:X}, V{y:X}"),
+            0x3 => format!("XOR V{x:X}, V{y:X}"),
+            0x4 => format!("ADD V{x:X}, V{y:X}"),
+            0x5 => format!("SUB V{x:X}, V{y:X}"),
+            0x6 => format!("SHR V{x:X}, V{y:X}"),
+            0x7 => format!("SUBN V{x:X}, V{y:X}"),
+            0xE => format!("SHL V{x:X}, V{y:X}"),
+            _ => format!("UNKNOWN {opcode:#06x}"),
+        },
+        0x9 => format!("SNE V{x:X}, V{y:X}"),
+        0xA => format!("LD I, {nnn:#05x}"),
+        0xB => format!("JP V0, {nnn:#05x}"),
+        0xC => format!("RND V{x:X}, {kk:#04x}"),
+        0xD => format!("DRW V{x:X}, V{y:X}, {n}"),
+        0xE => match opcode & 0x00FF {
+            0x9E => format!("SKP V{x:X}"),
+            0xA1 => format!("SKNP V{x:X}"),
+            _ => format!("UNKNOWN {opcode:#06x}"),
+        },
+        0xF => match opcode & 0x00FF {
+            0x07 => format!("LD V{x:X}, DT"),
+            0x0A => format!("LD V{x:X}, K"),
+            0x15 => format!("LD DT, V{x:X}"),
+            0x18 => format!("LD ST, V{x:X}"),
+            0x1E => format!("ADD I, V{x:X}"),
+            0x29 => format!("LD F, V{x:X}"),
+            0x30 => format!("LD HF, V{x:X}"),
+            0x33 => format!("LD B, V{x:X}"),
+            0x55 => format!("LD [I], V{x:X}"),
+            0x65 => format!("LD V{x:X}, [I]"),
+            _ => format!("UNKNOWN {opcode:#06x}"),
+        },
+        _ => format!("UNKNOWN {opcode:#06x}"),
+    }
+}
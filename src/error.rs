@@ -0,0 +1,36 @@
+use std::fmt;
+
+// Recoverable machine errors, surfaced instead of panicking so a bad ROM or
+// buggy opcode doesn't take down the whole process.
+#[derive(Debug)]
+pub enum ChipError {
+    UnknownOpcode(u16),
+    StackOverflow,
+    StackUnderflow,
+    RomTooLarge,
+    PcOutOfBounds,
+    Io(std::io::Error),
+    InvalidSaveState(&'static str),
+}
+
+impl fmt::Display for ChipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChipError::UnknownOpcode(opcode) => write!(f, "unknown opcode {opcode:#06x}"),
+            ChipError::StackOverflow => write!(f, "stack overflow"),
+            ChipError::StackUnderflow => write!(f, "stack underflow"),
+            ChipError::RomTooLarge => write!(f, "ROM too large to fit in memory"),
+            ChipError::PcOutOfBounds => write!(f, "program counter out of bounds"),
+            ChipError::Io(err) => write!(f, "I/O error: {err}"),
+            ChipError::InvalidSaveState(reason) => write!(f, "invalid save state: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ChipError {}
+
+impl From<std::io::Error> for ChipError {
+    fn from(err: std::io::Error) -> Self {
+        ChipError::Io(err)
+    }
+}
@@ -1,18 +1,111 @@
 use std::{
     fs::File,
-    io::Read,
+    io::{Read, Write},
     time::{Duration, Instant},
 };
 
 use sdl2::{
-    event::Event, keyboard::Keycode, pixels::Color, rect::Rect, render::Canvas, video::Window,
+    audio::AudioSpecDesired, event::Event, keyboard::Keycode, pixels::Color, rect::Rect,
+    render::Canvas, video::Window,
 };
 
 #[macro_use]
 mod macros;
 
+mod audio;
+mod debugger;
+mod error;
+
+use audio::SquareWave;
+use debugger::{disassemble, Debugger};
+use error::ChipError;
+
 const SCALE: u32 = 10;
 
+// SUPER-CHIP hi-res resolution. Base CHIP-8 uses the top-left 64x32 corner
+// of this same buffer.
+const HIRES_WIDTH: usize = 128;
+const HIRES_HEIGHT: usize = 64;
+const LORES_WIDTH: usize = 64;
+const LORES_HEIGHT: usize = 32;
+
+// SUPER-CHIP large font (0-9), 10 bytes per glyph, used by Fx30.
+const BIG_FONT: [u8; 100] = [
+    0xFF, 0xFF, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, // 0
+    0x18, 0x78, 0x78, 0x18, 0x18, 0x18, 0x18, 0x18, 0xFF, 0xFF, // 1
+    0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // 2
+    0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // 3
+    0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0x03, 0x03, // 4
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // 5
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, // 6
+    0xFF, 0xFF, 0x03, 0x03, 0x06, 0x0C, 0x18, 0x18, 0x18, 0x18, // 7
+    0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, // 8
+    0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // 9
+];
+// Large font lives right after the small font, which ends at 0xA0.
+const BIG_FONT_ADDR: u16 = 0xA0;
+
+// Save-state file format: a magic header, a version byte, then a
+// byte-for-byte dump of every ChipContext field except quirks, in
+// declaration order.
+const SAVE_STATE_MAGIC: &[u8; 4] = b"C8SS";
+const SAVE_STATE_VERSION: u8 = 1;
+
+// Compatibility toggles for the handful of opcodes where real interpreters
+// disagree. Defaults match modern CHIP-8 interpreters; flip individual
+// fields (or use one of the presets below) to run ROMs written against the
+// original COSMAC VIP or SUPER-CHIP behavior.
+#[derive(Clone, Copy)]
+struct Quirks {
+    // 8xy6/8xyE: copy Vy into Vx before shifting, instead of shifting Vx in place.
+    shift_uses_vy: bool,
+    // Fx55/Fx65: advance I by x + 1 after the load/store loop.
+    load_store_increments_i: bool,
+    // Bnnn: jump to V[x] + nnn (x = high nibble) instead of V0 + nnn.
+    jump_v0_uses_vx: bool,
+    // 8xy1/8xy2/8xy3: clear VF to 0 after OR/AND/XOR.
+    vf_reset_on_logic: bool,
+}
+
+impl Quirks {
+    fn new() -> Self {
+        Self {
+            shift_uses_vy: false,
+            load_store_increments_i: false,
+            jump_v0_uses_vx: false,
+            vf_reset_on_logic: false,
+        }
+    }
+
+    // Original COSMAC VIP behavior, as expected by most classic CHIP-8 ROMs.
+    fn chip8() -> Self {
+        Self {
+            shift_uses_vy: true,
+            load_store_increments_i: true,
+            jump_v0_uses_vx: false,
+            vf_reset_on_logic: true,
+        }
+    }
+
+    // SUPER-CHIP / modern interpreter behavior, as expected by most test ROMs.
+    fn superchip() -> Self {
+        Self {
+            shift_uses_vy: false,
+            load_store_increments_i: false,
+            jump_v0_uses_vx: true,
+            vf_reset_on_logic: false,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "chip8" => Some(Self::chip8()),
+            "superchip" | "schip" => Some(Self::superchip()),
+            _ => None,
+        }
+    }
+}
+
 struct ChipContext {
     // Memory
     memory: [u8; Kilobytes!(4)],
@@ -29,11 +122,17 @@ struct ChipContext {
     // Stack
     stack: [u16; 16],
 
-    // Display buffer on/off
-    framebuffer: [[bool; 32]; 64],
+    // Display buffer on/off, sized for the largest supported resolution.
+    // In lo-res (base CHIP-8) mode only the top-left 64x32 corner is used.
+    framebuffer: [[bool; HIRES_HEIGHT]; HIRES_WIDTH],
+    // SUPER-CHIP 128x64 hi-res mode toggle (00FF/00FE).
+    hires: bool,
 
     // Keys
     keyboard: [bool; 16],
+
+    // Compatibility toggles for opcodes with multiple real-world behaviors.
+    quirks: Quirks,
 }
 
 impl ChipContext {
@@ -47,8 +146,26 @@ impl ChipContext {
             sp: 0,
             i: 0,
             stack: [0; 16],
-            framebuffer: [[false; 32]; 64],
+            framebuffer: [[false; HIRES_HEIGHT]; HIRES_WIDTH],
+            hires: false,
             keyboard: [false; 16],
+            quirks: Quirks::new(),
+        }
+    }
+
+    fn screen_width(&self) -> usize {
+        if self.hires {
+            HIRES_WIDTH
+        } else {
+            LORES_WIDTH
+        }
+    }
+
+    fn screen_height(&self) -> usize {
+        if self.hires {
+            HIRES_HEIGHT
+        } else {
+            LORES_HEIGHT
         }
     }
 
@@ -71,13 +188,110 @@ impl ChipContext {
             0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
             0xF0, 0x80, 0xF0, 0x80, 0x80, // F
         ]);
+        self.memory[(BIG_FONT_ADDR as usize)..(BIG_FONT_ADDR as usize + BIG_FONT.len())]
+            .copy_from_slice(&BIG_FONT);
+    }
+
+    fn save_state(&self, path: &str) -> Result<(), ChipError> {
+        let mut file = File::create(path)?;
+        file.write_all(SAVE_STATE_MAGIC)?;
+        file.write_all(&[SAVE_STATE_VERSION])?;
+        file.write_all(&self.memory)?;
+        file.write_all(&self.v)?;
+        file.write_all(&[self.dt, self.st])?;
+        file.write_all(&self.pc.to_le_bytes())?;
+        file.write_all(&[self.sp])?;
+        file.write_all(&self.i.to_le_bytes())?;
+        for slot in self.stack.iter() {
+            file.write_all(&slot.to_le_bytes())?;
+        }
+        for column in self.framebuffer.iter() {
+            for &pixel in column.iter() {
+                file.write_all(&[pixel as u8])?;
+            }
+        }
+        file.write_all(&[self.hires as u8])?;
+        for &key in self.keyboard.iter() {
+            file.write_all(&[key as u8])?;
+        }
+        Ok(())
     }
 
-    fn load_rom(&mut self, path: &str) {
-        let mut file = File::open(path).expect("Erro when open ROM");
-        file.read(&mut self.memory[0x200..Kilobytes!(4)])
-            .expect("Erro when read");
+    fn load_state(&mut self, path: &str) -> Result<(), ChipError> {
+        if !std::path::Path::new(path).exists() {
+            return Err(ChipError::InvalidSaveState("no save state file yet"));
+        }
+        let mut file = File::open(path)?;
+
+        let mut magic = [0u8; 4];
+        file.read_exact(&mut magic)?;
+        if &magic != SAVE_STATE_MAGIC {
+            return Err(ChipError::InvalidSaveState("not a CHIP-8 save state file"));
+        }
+
+        let mut version = [0u8; 1];
+        file.read_exact(&mut version)?;
+        if version[0] != SAVE_STATE_VERSION {
+            return Err(ChipError::InvalidSaveState("unsupported save state version"));
+        }
+
+        file.read_exact(&mut self.memory)?;
+        file.read_exact(&mut self.v)?;
+
+        let mut dt_st = [0u8; 2];
+        file.read_exact(&mut dt_st)?;
+        self.dt = dt_st[0];
+        self.st = dt_st[1];
+
+        let mut pc_bytes = [0u8; 2];
+        file.read_exact(&mut pc_bytes)?;
+        self.pc = u16::from_le_bytes(pc_bytes);
+
+        let mut sp_byte = [0u8; 1];
+        file.read_exact(&mut sp_byte)?;
+        self.sp = sp_byte[0];
+
+        let mut i_bytes = [0u8; 2];
+        file.read_exact(&mut i_bytes)?;
+        self.i = u16::from_le_bytes(i_bytes);
+
+        for slot in self.stack.iter_mut() {
+            let mut bytes = [0u8; 2];
+            file.read_exact(&mut bytes)?;
+            *slot = u16::from_le_bytes(bytes);
+        }
+
+        for column in self.framebuffer.iter_mut() {
+            for pixel in column.iter_mut() {
+                let mut byte = [0u8; 1];
+                file.read_exact(&mut byte)?;
+                *pixel = byte[0] != 0;
+            }
+        }
+
+        let mut hires_byte = [0u8; 1];
+        file.read_exact(&mut hires_byte)?;
+        self.hires = hires_byte[0] != 0;
+
+        for key in self.keyboard.iter_mut() {
+            let mut byte = [0u8; 1];
+            file.read_exact(&mut byte)?;
+            *key = byte[0] != 0;
+        }
+
+        Ok(())
+    }
+
+    fn load_rom(&mut self, path: &str) -> Result<(), ChipError> {
+        let mut file = File::open(path)?;
+        let rom_capacity = self.memory.len() - 0x200;
+        let rom_size = file.metadata()?.len() as usize;
+        if rom_size > rom_capacity {
+            return Err(ChipError::RomTooLarge);
+        }
+        file.read(&mut self.memory[0x200..Kilobytes!(4)])?;
         self.pc = 0x200;
+        Ok(())
     }
 }
 
@@ -91,9 +305,46 @@ fn clean_screen(chip: &mut ChipContext) {
 }
 
 // 00EE - RET
-fn return_sbr(chip: &mut ChipContext) {
+fn return_sbr(chip: &mut ChipContext) -> Result<(), ChipError> {
+    if chip.sp == 0 {
+        return Err(ChipError::StackUnderflow);
+    }
     chip.sp -= 1;
     chip.pc = chip.stack[chip.sp as usize];
+    Ok(())
+}
+
+// 00Cn - SCD n (scroll display down n pixels)
+fn scroll_down(chip: &mut ChipContext, n: u8) {
+    let width = chip.screen_width();
+    let height = chip.screen_height();
+    for y in (0..height).rev() {
+        for x in 0..width {
+            chip.framebuffer[x][y] = y >= n as usize && chip.framebuffer[x][y - n as usize];
+        }
+    }
+}
+
+// 00FB - SCR (scroll display right 4 pixels)
+fn scroll_right(chip: &mut ChipContext) {
+    let width = chip.screen_width();
+    let height = chip.screen_height();
+    for y in 0..height {
+        for x in (0..width).rev() {
+            chip.framebuffer[x][y] = x >= 4 && chip.framebuffer[x - 4][y];
+        }
+    }
+}
+
+// 00FC - SCL (scroll display left 4 pixels)
+fn scroll_left(chip: &mut ChipContext) {
+    let width = chip.screen_width();
+    let height = chip.screen_height();
+    for y in 0..height {
+        for x in 0..width {
+            chip.framebuffer[x][y] = x + 4 < width && chip.framebuffer[x + 4][y];
+        }
+    }
 }
 
 // 1nnn - JP addr
@@ -102,10 +353,14 @@ fn jump(chip: &mut ChipContext, nnn: u16) {
 }
 
 // 2nnn - CALL addr
-fn call_sbr(chip: &mut ChipContext, nnn: u16) {
+fn call_sbr(chip: &mut ChipContext, nnn: u16) -> Result<(), ChipError> {
+    if chip.sp as usize >= chip.stack.len() {
+        return Err(ChipError::StackOverflow);
+    }
     chip.stack[chip.sp as usize] = chip.pc;
     chip.sp += 1;
     chip.pc = nnn;
+    Ok(())
 }
 
 // 3xkk - SE Vx, byte
@@ -148,16 +403,25 @@ fn load_vx_vy(chip: &mut ChipContext, x: u8, y: u8) {
 // 8xy1 - OR Vx, Vy
 fn or(chip: &mut ChipContext, x: u8, y: u8) {
     chip.v[x as usize] |= chip.v[y as usize];
+    if chip.quirks.vf_reset_on_logic {
+        chip.v[0xF] = 0;
+    }
 }
 
 // 8xy2 - AND Vx, Vy
 fn and(chip: &mut ChipContext, x: u8, y: u8) {
     chip.v[x as usize] &= chip.v[y as usize];
+    if chip.quirks.vf_reset_on_logic {
+        chip.v[0xF] = 0;
+    }
 }
 
 // 8xy3 - XOR Vx, Vy
 fn xor(chip: &mut ChipContext, x: u8, y: u8) {
     chip.v[x as usize] ^= chip.v[y as usize];
+    if chip.quirks.vf_reset_on_logic {
+        chip.v[0xF] = 0;
+    }
 }
 
 // 8xy4 - ADD Vx, Vy
@@ -179,7 +443,10 @@ fn sub(chip: &mut ChipContext, x: u8, y: u8) {
 }
 
 // 8xy6 - SHR Vx Vy
-fn shr(chip: &mut ChipContext, x: u8, _y: u8) {
+fn shr(chip: &mut ChipContext, x: u8, y: u8) {
+    if chip.quirks.shift_uses_vy {
+        chip.v[x as usize] = chip.v[y as usize];
+    }
     chip.v[0xF] = chip.v[x as usize] & 0x1;
     chip.v[x as usize] >>= 1;
 }
@@ -196,7 +463,10 @@ fn subn(chip: &mut ChipContext, x: u8, y: u8) {
 }
 
 // 8xyE - SHL Vx Vy
-fn shl(chip: &mut ChipContext, x: u8, _y: u8) {
+fn shl(chip: &mut ChipContext, x: u8, y: u8) {
+    if chip.quirks.shift_uses_vy {
+        chip.v[x as usize] = chip.v[y as usize];
+    }
     chip.v[0xF] = (chip.v[x as usize] & 0x80) >> 7;
     chip.v[x as usize] <<= 1;
 }
@@ -214,8 +484,9 @@ fn set_index(chip: &mut ChipContext, nnn: u16) {
 }
 
 // Bnnn - JP V0, addr
-fn jump_v0(chip: &mut ChipContext, nnn: u16) {
-    chip.pc = nnn + (chip.v[0] as u16);
+fn jump_v0(chip: &mut ChipContext, x: u8, nnn: u16) {
+    let reg = if chip.quirks.jump_v0_uses_vx { x } else { 0 };
+    chip.pc = nnn + (chip.v[reg as usize] as u16);
 }
 
 // Cxkk - RND Vx, byte
@@ -226,21 +497,30 @@ fn rnd(chip: &mut ChipContext, x: u8, kk: u8) {
 
 // Dxyn - DRW Vx, Vy, nibble
 fn draw(chip: &mut ChipContext, x: u8, y: u8, n: u8) {
-    let x_coord: usize = (chip.v[x as usize] % 64) as usize;
-    let mut y_coord: usize = (chip.v[y as usize] % 32) as usize;
+    let width = chip.screen_width();
+    let height = chip.screen_height();
+    let x_coord = (chip.v[x as usize] as usize) % width;
+    let mut y_coord = (chip.v[y as usize] as usize) % height;
     chip.v[0xF] = 0;
-    for offset in 0..n {
+
+    // In hi-res mode, a height nibble of 0 draws a SUPER-CHIP 16x16 sprite
+    // (16 rows of 2 bytes); in lo-res mode it draws nothing.
+    let (rows, bytes_per_row) = if n == 0 && chip.hires { (16, 2) } else { (n, 1) };
+
+    for row in 0..rows {
         let mut x_temp = x_coord;
-        for bitset in (0..8).rev() {
-            let swap_pixel: bool =
-                ((chip.memory[(chip.i + (offset as u16)) as usize] >> bitset) & 1) != 0;
-            if chip.framebuffer[x_temp][y_coord] && swap_pixel {
-                chip.v[0xF] = 1;
+        for byte in 0..bytes_per_row {
+            let addr = chip.i + (row as u16) * (bytes_per_row as u16) + (byte as u16);
+            for bitset in (0..8).rev() {
+                let swap_pixel: bool = ((chip.memory[addr as usize] >> bitset) & 1) != 0;
+                if chip.framebuffer[x_temp][y_coord] && swap_pixel {
+                    chip.v[0xF] = 1;
+                }
+                chip.framebuffer[x_temp][y_coord] ^= swap_pixel;
+                x_temp = (x_temp + 1) % width;
             }
-            chip.framebuffer[x_temp][y_coord] = chip.framebuffer[x_temp][y_coord] ^ swap_pixel;
-            x_temp = (x_temp + 1) % 64;
         }
-        y_coord = (y_coord + 1) % 32;
+        y_coord = (y_coord + 1) % height;
     }
 }
 
@@ -299,6 +579,11 @@ fn load_f_vx(chip: &mut ChipContext, x: u8) {
     chip.i = 0x50 + (chip.v[x as usize] as u16) * 5;
 }
 
+// Fx30 - LD HF, Vx (SUPER-CHIP large font)
+fn load_hf_vx(chip: &mut ChipContext, x: u8) {
+    chip.i = BIG_FONT_ADDR + (chip.v[x as usize] as u16) * 10;
+}
+
 // Fx33 - LD B, Vx
 fn load_b_vx(chip: &mut ChipContext, x: u8) {
     chip.memory[chip.i as usize] = chip.v[x as usize] / 100;
@@ -311,6 +596,9 @@ fn load_i_vx(chip: &mut ChipContext, x: u8) {
     for i in 0..=x {
         chip.memory[(chip.i + i as u16) as usize] = chip.v[i as usize];
     }
+    if chip.quirks.load_store_increments_i {
+        chip.i += x as u16 + 1;
+    }
 }
 
 // Fx65 - LD Vx, [I]
@@ -318,6 +606,9 @@ fn load_vx_i(chip: &mut ChipContext, x: u8) {
     for i in 0..=x {
         chip.v[i as usize] = chip.memory[(chip.i + i as u16) as usize];
     }
+    if chip.quirks.load_store_increments_i {
+        chip.i += x as u16 + 1;
+    }
 }
 
 fn render(chip: &ChipContext, canvas: &mut Canvas<Window>) {
@@ -326,10 +617,21 @@ fn render(chip: &ChipContext, canvas: &mut Canvas<Window>) {
 
     canvas.set_draw_color(Color::RGB(255, 255, 255));
 
-    for y in (0..32).map(|x| x as u32) {
-        for x in (0..64).map(|y| y as u32) {
-            if chip.framebuffer[x as usize][y as usize] {
-                let rect = Rect::new((x * SCALE) as i32, (y * SCALE) as i32, SCALE, SCALE);
+    let width = chip.screen_width();
+    let height = chip.screen_height();
+    // The window is sized for hi-res; in lo-res each CHIP-8 pixel is drawn
+    // twice as large so the picture still fills it.
+    let pixel_size = if chip.hires { SCALE } else { SCALE * 2 };
+
+    for y in 0..height {
+        for x in 0..width {
+            if chip.framebuffer[x][y] {
+                let rect = Rect::new(
+                    (x as u32 * pixel_size) as i32,
+                    (y as u32 * pixel_size) as i32,
+                    pixel_size,
+                    pixel_size,
+                );
                 canvas.fill_rect(rect).unwrap();
             }
         }
@@ -337,6 +639,20 @@ fn render(chip: &ChipContext, canvas: &mut Canvas<Window>) {
     canvas.present();
 }
 
+// Prints the disassembly of the instruction about to execute along with
+// the current registers and stack, used by the --debug stepping debugger.
+fn print_debug_state(chip: &ChipContext) {
+    let opcode = fetch_opcode(chip);
+    println!("{:#06x}: {}", chip.pc, disassemble(opcode));
+    println!("Registers: {:?}", chip.v);
+    println!(
+        "I: {:#06x}  SP: {:#04x}  DT: {:#04x}  ST: {:#04x}",
+        chip.i, chip.sp, chip.dt, chip.st
+    );
+    println!("Stack: {:?}", chip.stack);
+    println!();
+}
+
 fn key2btn(key: Keycode) -> Option<usize> {
     match key {
         Keycode::Num1 => Some(0x1),
@@ -359,17 +675,157 @@ fn key2btn(key: Keycode) -> Option<usize> {
     }
 }
 
-// function to clear and show all informations about memory, register and stack in terminal each cycle of loop
-fn debug(chip: &ChipContext) {
-    println!("PC: {:#04x}", chip.pc);
-    println!("I: {:#04x}", chip.i);
-    println!("SP: {:#04x}", chip.sp);
-    println!("DT: {:#04x}", chip.dt);
-    println!("ST: {:#04x}", chip.st);
-    println!("Registers: {:?}", chip.v);
-    println!("Stack: {:?}", chip.stack);
-    println!("Keyboard: {:?}", chip.keyboard);
-    println!();
+// Reads the opcode at chip.pc without advancing it.
+fn fetch_opcode(chip: &ChipContext) -> u16 {
+    let byte1 = chip.memory[chip.pc as usize];
+    let byte2 = chip.memory[(chip.pc + 1) as usize];
+    ((byte1 as u16) << 8) | byte2 as u16
+}
+
+// Fetches, decodes and executes the single opcode at chip.pc.
+fn step(chip: &mut ChipContext) -> Result<(), ChipError> {
+    if chip.pc as usize + 1 >= chip.memory.len() {
+        return Err(ChipError::PcOutOfBounds);
+    }
+
+    let opcode = fetch_opcode(chip);
+
+    chip.pc += 2;
+
+    match opcode >> 12 {
+        0x0 => match opcode & 0x00FF {
+            0x00 => (),
+            0xE0 => clean_screen(chip),
+            0xEE => return_sbr(chip)?,
+            0xFB => scroll_right(chip),
+            0xFC => scroll_left(chip),
+            0xFD => std::process::exit(0),
+            0xFE => {
+                chip.hires = false;
+                clean_screen(chip);
+            }
+            0xFF => {
+                chip.hires = true;
+                clean_screen(chip);
+            }
+            low if (low & 0x00F0) == 0x00C0 => scroll_down(chip, (low & 0x000F) as u8),
+            _ => return Err(ChipError::UnknownOpcode(opcode)),
+        },
+        0x1 => jump(chip, opcode & 0x0FFF),
+        0x2 => call_sbr(chip, opcode & 0x0FFF)?,
+        0x3 => skip_if(
+            chip,
+            ((opcode & 0x0F00) >> 8) as u8,
+            (opcode & 0x00FF) as u8,
+        ),
+        0x4 => skip_diff(
+            chip,
+            ((opcode & 0x0F00) >> 8) as u8,
+            (opcode & 0x00FF) as u8,
+        ),
+        0x5 => skip_equals(
+            chip,
+            ((opcode & 0x0F00) >> 8) as u8,
+            ((opcode & 0x00F0) >> 4) as u8,
+        ),
+        0x6 => load(
+            chip,
+            ((opcode & 0x0F00) >> 8) as u8,
+            (opcode & 0x00FF) as u8,
+        ),
+        0x7 => add(
+            chip,
+            ((opcode & 0x0F00) >> 8) as u8,
+            (opcode & 0x00FF) as u8,
+        ),
+        0x8 => match opcode & 0x000F {
+            0x0 => load_vx_vy(
+                chip,
+                ((opcode & 0x0F00) >> 8) as u8,
+                ((opcode & 0x00F0) >> 4) as u8,
+            ),
+            0x1 => or(
+                chip,
+                ((opcode & 0x0F00) >> 8) as u8,
+                ((opcode & 0x00F0) >> 4) as u8,
+            ),
+            0x2 => and(
+                chip,
+                ((opcode & 0x0F00) >> 8) as u8,
+                ((opcode & 0x00F0) >> 4) as u8,
+            ),
+            0x3 => xor(
+                chip,
+                ((opcode & 0x0F00) >> 8) as u8,
+                ((opcode & 0x00F0) >> 4) as u8,
+            ),
+            0x4 => add_vx_vy(
+                chip,
+                ((opcode & 0x0F00) >> 8) as u8,
+                ((opcode & 0x00F0) >> 4) as u8,
+            ),
+            0x5 => sub(
+                chip,
+                ((opcode & 0x0F00) >> 8) as u8,
+                ((opcode & 0x00F0) >> 4) as u8,
+            ),
+            0x6 => shr(
+                chip,
+                ((opcode & 0x0F00) >> 8) as u8,
+                ((opcode & 0x00F0) >> 4) as u8,
+            ),
+            0x7 => subn(
+                chip,
+                ((opcode & 0x0F00) >> 8) as u8,
+                ((opcode & 0x00F0) >> 4) as u8,
+            ),
+            0xE => shl(
+                chip,
+                ((opcode & 0x0F00) >> 8) as u8,
+                ((opcode & 0x00F0) >> 4) as u8,
+            ),
+            _ => return Err(ChipError::UnknownOpcode(opcode)),
+        },
+        0x9 => sne(
+            chip,
+            ((opcode & 0x0F00) >> 8) as u8,
+            ((opcode & 0x00F0) >> 4) as u8,
+        ),
+        0xA => set_index(chip, opcode & 0x0FFF),
+        0xB => jump_v0(chip, ((opcode & 0x0F00) >> 8) as u8, opcode & 0x0FFF),
+        0xC => rnd(
+            chip,
+            ((opcode & 0x0F00) >> 8) as u8,
+            (opcode & 0x00FF) as u8,
+        ),
+        0xD => draw(
+            chip,
+            ((opcode & 0x0F00) >> 8) as u8,
+            ((opcode & 0x00F0) >> 4) as u8,
+            (opcode & 0x000F) as u8,
+        ),
+        0xE => match opcode & 0x00FF {
+            0x9E => skip_key(chip, ((opcode & 0x0F00) >> 8) as u8),
+            0xA1 => skip_not_key(chip, ((opcode & 0x0F00) >> 8) as u8),
+            _ => return Err(ChipError::UnknownOpcode(opcode)),
+        },
+        0xF => match opcode & 0x00FF {
+            0x07 => load_vx_dt(chip, ((opcode & 0x0F00) >> 8) as u8),
+            0x0A => load_key(chip, ((opcode & 0x0F00) >> 8) as u8),
+            0x15 => load_dt_vx(chip, ((opcode & 0x0F00) >> 8) as u8),
+            0x18 => load_st_vx(chip, ((opcode & 0x0F00) >> 8) as u8),
+            0x1E => add_i_vx(chip, ((opcode & 0x0F00) >> 8) as u8),
+            0x29 => load_f_vx(chip, ((opcode & 0x0F00) >> 8) as u8),
+            0x30 => load_hf_vx(chip, ((opcode & 0x0F00) >> 8) as u8),
+            0x33 => load_b_vx(chip, ((opcode & 0x0F00) >> 8) as u8),
+            0x55 => load_i_vx(chip, ((opcode & 0x0F00) >> 8) as u8),
+            0x65 => load_vx_i(chip, ((opcode & 0x0F00) >> 8) as u8),
+            _ => return Err(ChipError::UnknownOpcode(opcode)),
+        },
+        _ => return Err(ChipError::UnknownOpcode(opcode)),
+    }
+
+    Ok(())
 }
 
 fn main() -> Result<(), String> {
@@ -377,7 +833,11 @@ fn main() -> Result<(), String> {
     let video_subsystem = sdl_context.video()?;
 
     let window = video_subsystem
-        .window("rust-sdl2 demo: Video", 64 * SCALE, 32 * SCALE)
+        .window(
+            "rust-sdl2 demo: Video",
+            HIRES_WIDTH as u32 * SCALE,
+            HIRES_HEIGHT as u32 * SCALE,
+        )
         .position_centered()
         .opengl()
         .build()
@@ -390,17 +850,60 @@ fn main() -> Result<(), String> {
     canvas.present();
     let mut event_pump = sdl_context.event_pump()?;
 
+    let audio_subsystem = sdl_context.audio()?;
+    let audio_spec = AudioSpecDesired {
+        freq: Some(44_100),
+        channels: Some(1),
+        samples: None,
+    };
+    let audio_device = audio_subsystem.open_playback(None, &audio_spec, |spec| SquareWave {
+        phase_inc: 440.0 / spec.freq as f32,
+        phase: 0.0,
+        volume: 0.25,
+    })?;
+
     let mut chip = ChipContext::new();
     chip.load_font();
 
     let mut args = std::env::args();
     args.next();
     let rom_path = args.next().expect("ROM path not provided");
-    chip.load_rom(&rom_path);
+
+    let mut cycles_per_frame: u32 = 11;
+    let mut debug_enabled = false;
+    let mut breakpoint: Option<u16> = None;
+    for arg in args {
+        if let Some(preset) = arg.strip_prefix("--quirks=") {
+            chip.quirks = Quirks::from_name(preset).unwrap_or_else(|| {
+                panic!("Unknown quirks preset: {preset} (expected one of: chip8, superchip)")
+            });
+        } else if let Some(rate) = arg.strip_prefix("--cycles=") {
+            cycles_per_frame = rate.parse().expect("Invalid --cycles value");
+        } else if arg == "--debug" {
+            debug_enabled = true;
+        } else if let Some(addr) = arg.strip_prefix("--break=") {
+            let addr = addr.strip_prefix("0x").unwrap_or(addr);
+            breakpoint = Some(u16::from_str_radix(addr, 16).expect("Invalid --break value"));
+        }
+    }
+
+    if let Err(err) = chip.load_rom(&rom_path) {
+        eprintln!("Failed to load ROM: {err}");
+        std::process::exit(1);
+    }
     chip.pc = 0x200;
 
-    let mut last_update = Instant::now();
-    let clock = Duration::from_millis(1000 / 60);
+    let save_path = format!("{rom_path}.sav");
+
+    let mut debugger = Debugger::new(debug_enabled);
+    debugger.breakpoint = breakpoint;
+    if debugger.paused {
+        print_debug_state(&chip);
+    }
+
+    let frame_duration = Duration::from_millis(1000 / 60);
+    let mut last_frame = Instant::now();
+    let mut halted = false;
 
     'running: loop {
         for event in event_pump.poll_iter() {
@@ -410,6 +913,46 @@ fn main() -> Result<(), String> {
                     keycode: Some(Keycode::Escape),
                     ..
                 } => break 'running,
+                Event::KeyDown {
+                    keycode: Some(Keycode::F5),
+                    ..
+                } => {
+                    if let Err(err) = chip.save_state(&save_path) {
+                        eprintln!("Failed to save state: {err}");
+                    }
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::F9),
+                    ..
+                } => {
+                    if let Err(err) = chip.load_state(&save_path) {
+                        eprintln!("Failed to load save state: {err}");
+                    }
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::P),
+                    ..
+                } if debugger.enabled && !debugger.paused => {
+                    debugger.paused = true;
+                    print_debug_state(&chip);
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::Return),
+                    ..
+                } if debugger.enabled && debugger.paused => {
+                    debugger.paused = false;
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::N),
+                    ..
+                } if debugger.enabled && debugger.paused && !halted => {
+                    let pc = chip.pc;
+                    if let Err(err) = step(&mut chip) {
+                        halted = true;
+                        eprintln!("{err} at pc {pc:#06x}");
+                    }
+                    print_debug_state(&chip);
+                }
                 Event::KeyDown {
                     keycode: Some(key), ..
                 } => {
@@ -428,142 +971,42 @@ fn main() -> Result<(), String> {
             }
         }
 
-        let byte1 = chip.memory[chip.pc as usize];
-        let byte2 = chip.memory[(chip.pc + 1) as usize];
+        if !halted && !(debugger.enabled && debugger.paused) {
+            for _ in 0..cycles_per_frame {
+                if debugger.enabled && debugger.breakpoint == Some(chip.pc) {
+                    debugger.paused = true;
+                    println!("Breakpoint hit.");
+                    print_debug_state(&chip);
+                    break;
+                }
 
-        let opcode = ((byte1 as u16) << 8) | byte2 as u16;
+                let pc = chip.pc;
+                if let Err(err) = step(&mut chip) {
+                    halted = true;
+                    let title = format!("chip8 - {err} at pc {pc:#06x}");
+                    eprintln!("{title}");
+                    canvas.window_mut().set_title(&title).ok();
+                    break;
+                }
+            }
+        }
 
-        chip.pc += 2;
+        chip.dt = if chip.dt > 0 { chip.dt - 1 } else { 0 };
+        chip.st = if chip.st > 0 { chip.st - 1 } else { 0 };
 
-        match opcode >> 12 {
-            0x0 => match opcode & 0x00FF {
-                0x00 => (),
-                0xE0 => clean_screen(&mut chip),
-                0xEE => return_sbr(&mut chip),
-                _ => panic!("Invalid opcode: {:#04x}", opcode),
-            },
-            0x1 => jump(&mut chip, opcode & 0x0FFF),
-            0x2 => call_sbr(&mut chip, opcode & 0x0FFF),
-            0x3 => skip_if(
-                &mut chip,
-                ((opcode & 0x0F00) >> 8) as u8,
-                (opcode & 0x00FF) as u8,
-            ),
-            0x4 => skip_diff(
-                &mut chip,
-                ((opcode & 0x0F00) >> 8) as u8,
-                (opcode & 0x00FF) as u8,
-            ),
-            0x5 => skip_equals(
-                &mut chip,
-                ((opcode & 0x0F00) >> 8) as u8,
-                ((opcode & 0x00F0) >> 4) as u8,
-            ),
-            0x6 => load(
-                &mut chip,
-                ((opcode & 0x0F00) >> 8) as u8,
-                (opcode & 0x00FF) as u8,
-            ),
-            0x7 => add(
-                &mut chip,
-                ((opcode & 0x0F00) >> 8) as u8,
-                (opcode & 0x00FF) as u8,
-            ),
-            0x8 => match opcode & 0x000F {
-                0x0 => load_vx_vy(
-                    &mut chip,
-                    ((opcode & 0x0F00) >> 8) as u8,
-                    ((opcode & 0x00F0) >> 4) as u8,
-                ),
-                0x1 => or(
-                    &mut chip,
-                    ((opcode & 0x0F00) >> 8) as u8,
-                    ((opcode & 0x00F0) >> 4) as u8,
-                ),
-                0x2 => and(
-                    &mut chip,
-                    ((opcode & 0x0F00) >> 8) as u8,
-                    ((opcode & 0x00F0) >> 4) as u8,
-                ),
-                0x3 => xor(
-                    &mut chip,
-                    ((opcode & 0x0F00) >> 8) as u8,
-                    ((opcode & 0x00F0) >> 4) as u8,
-                ),
-                0x4 => add_vx_vy(
-                    &mut chip,
-                    ((opcode & 0x0F00) >> 8) as u8,
-                    ((opcode & 0x00F0) >> 4) as u8,
-                ),
-                0x5 => sub(
-                    &mut chip,
-                    ((opcode & 0x0F00) >> 8) as u8,
-                    ((opcode & 0x00F0) >> 4) as u8,
-                ),
-                0x6 => shr(
-                    &mut chip,
-                    ((opcode & 0x0F00) >> 8) as u8,
-                    ((opcode & 0x00F0) >> 4) as u8,
-                ),
-                0x7 => subn(
-                    &mut chip,
-                    ((opcode & 0x0F00) >> 8) as u8,
-                    ((opcode & 0x00F0) >> 4) as u8,
-                ),
-                0xE => shl(
-                    &mut chip,
-                    ((opcode & 0x0F00) >> 8) as u8,
-                    ((opcode & 0x00F0) >> 4) as u8,
-                ),
-                _ => panic!("Invalid opcode: {:#04x}", opcode),
-            },
-            0x9 => sne(
-                &mut chip,
-                ((opcode & 0x0F00) >> 8) as u8,
-                ((opcode & 0x00F0) >> 4) as u8,
-            ),
-            0xA => set_index(&mut chip, opcode & 0x0FFF),
-            0xB => jump_v0(&mut chip, opcode & 0x0FFF),
-            0xC => rnd(
-                &mut chip,
-                ((opcode & 0x0F00) >> 8) as u8,
-                (opcode & 0x00FF) as u8,
-            ),
-            0xD => draw(
-                &mut chip,
-                ((opcode & 0x0F00) >> 8) as u8,
-                ((opcode & 0x00F0) >> 4) as u8,
-                (opcode & 0x000F) as u8,
-            ),
-            0xE => match opcode & 0x00FF {
-                0x9E => skip_key(&mut chip, ((opcode & 0x0F00) >> 8) as u8),
-                0xA1 => skip_not_key(&mut chip, ((opcode & 0x0F00) >> 8) as u8),
-                _ => panic!("Invalid opcode: {:#04x}", opcode),
-            },
-            0xF => match opcode & 0x00FF {
-                0x07 => load_vx_dt(&mut chip, ((opcode & 0x0F00) >> 8) as u8),
-                0x0A => load_key(&mut chip, ((opcode & 0x0F00) >> 8) as u8),
-                0x15 => load_dt_vx(&mut chip, ((opcode & 0x0F00) >> 8) as u8),
-                0x18 => load_st_vx(&mut chip, ((opcode & 0x0F00) >> 8) as u8),
-                0x1E => add_i_vx(&mut chip, ((opcode & 0x0F00) >> 8) as u8),
-                0x29 => load_f_vx(&mut chip, ((opcode & 0x0F00) >> 8) as u8),
-                0x33 => load_b_vx(&mut chip, ((opcode & 0x0F00) >> 8) as u8),
-                0x55 => load_i_vx(&mut chip, ((opcode & 0x0F00) >> 8) as u8),
-                0x65 => load_vx_i(&mut chip, ((opcode & 0x0F00) >> 8) as u8),
-                _ => panic!("Invalid opcode: {:#04x}", opcode),
-            },
-            _ => panic!("Invalid opcode: {:#04x}", opcode),
+        if chip.st > 0 {
+            audio_device.resume();
+        } else {
+            audio_device.pause();
         }
+
         render(&chip, &mut canvas);
 
-        if last_update.elapsed() >= clock {
-            chip.dt = if chip.dt > 0 { chip.dt - 1 } else { 0 };
-            chip.st = if chip.st > 0 { chip.st - 1 } else { 0 };
-            last_update = Instant::now();
+        let elapsed = last_frame.elapsed();
+        if elapsed < frame_duration {
+            std::thread::sleep(frame_duration - elapsed);
         }
-
-        std::thread::sleep(std::time::Duration::from_millis(1));
-        debug(&chip);
+        last_frame = Instant::now();
     }
     Ok(())
 }